@@ -0,0 +1,83 @@
+use crate::ast;
+use crate::ast::{Delimiter, Kind};
+use crate::error::ParseError;
+use crate::parser::Parser;
+use crate::traits::{Parse, Peek};
+use runestick::Span;
+
+/// An outer attribute, e.g. `#[foo(bar)]`, or a `///` doc comment (which is
+/// lexed as sugar for `#[doc = "..."]`).
+///
+/// # Examples
+///
+/// ```rust
+/// use rune::{parse_all, ast};
+///
+/// parse_all::<ast::Attribute>("#[foo]").unwrap();
+/// parse_all::<ast::Attribute>("#[foo(bar, baz)]").unwrap();
+/// parse_all::<ast::Attribute>("/// a doc comment").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    span: Span,
+}
+
+impl Attribute {
+    /// Get the span of the attribute.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Peek for Attribute {
+    fn peek(t1: Option<ast::Token>, _t2: Option<ast::Token>) -> bool {
+        matches!(
+            t1.map(|t| t.kind),
+            Some(Kind::Pound) | Some(Kind::DocComment)
+        )
+    }
+}
+
+impl Parse for Attribute {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        let token = parser.token_peek_eof()?;
+
+        if let Kind::DocComment = token.kind {
+            parser.token_next()?;
+            return Ok(Self { span: token.span });
+        }
+
+        let pound = parser.parse::<ast::Pound>()?;
+        parser.parse::<ast::OpenBracket>()?;
+
+        // The contents of the attribute aren't interpreted here, just
+        // skipped over, tracking nesting so a `]` inside e.g. a nested
+        // array literal doesn't end the attribute early.
+        let mut depth = 1usize;
+        let close_span;
+
+        loop {
+            let token = parser.token_peek_eof()?;
+
+            match token.kind {
+                Kind::Open(Delimiter::Bracket) => depth += 1,
+                Kind::Close(Delimiter::Bracket) => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        close_span = token.span;
+                        parser.token_next()?;
+                        break;
+                    }
+                }
+                _ => (),
+            }
+
+            parser.token_next()?;
+        }
+
+        Ok(Self {
+            span: pound.span().join(close_span),
+        })
+    }
+}
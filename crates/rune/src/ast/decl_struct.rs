@@ -8,10 +8,16 @@ use runestick::Span;
 /// A struct declaration.
 #[derive(Debug, Clone)]
 pub struct DeclStruct {
+    /// Outer attributes and doc comments on the declaration.
+    pub attributes: Vec<ast::Attribute>,
+    /// The visibility of the declaration.
+    pub visibility: ast::Visibility,
     /// The `struct` keyword.
     pub struct_: ast::Struct,
     /// The identifier of the struct declaration.
     pub ident: ast::Ident,
+    /// Generic parameters of the declaration, e.g. `<T, U>`.
+    pub generics: Option<ast::Generics>,
     /// The body of the struct.
     pub body: DeclStructBody,
 }
@@ -19,7 +25,15 @@ pub struct DeclStruct {
 impl DeclStruct {
     /// Get the span for the declaration.
     pub fn span(&self) -> Span {
-        let start = self.struct_.span();
+        let mut start = self.struct_.span();
+
+        if let Some(visibility_span) = self.visibility.span() {
+            start = visibility_span.join(start);
+        }
+
+        if let Some(attribute) = self.attributes.first() {
+            start = attribute.span().join(start);
+        }
 
         match &self.body {
             DeclStructBody::EmptyBody(..) => start,
@@ -44,12 +58,47 @@ impl DeclStruct {
 /// parse_all::<ast::DeclStruct>("struct Foo").unwrap();
 /// parse_all::<ast::DeclStruct>("struct Foo ( a, b, c )").unwrap();
 /// parse_all::<ast::DeclStruct>("struct Foo { a, b, c }").unwrap();
+/// parse_all::<ast::DeclStruct>("pub struct Foo<T, U> { a, b, c }").unwrap();
+/// parse_all::<ast::DeclStruct>("#[derive(Debug)] struct Foo { a }").unwrap();
+/// ```
+///
+/// A malformed field no longer aborts the whole declaration. Instead the
+/// parser recovers at the next `,` or closing delimiter and keeps going, so
+/// a single compile can surface every diagnostic instead of just the first.
+/// Recovery covers a field that fails to parse; a field that parses but is
+/// followed by something other than `,` or the closing delimiter (e.g.
+/// `bad(1, 2)`) still fails the whole declaration:
+///
+/// ```rust
+/// use rune::{parse_all_recovering, ast};
+///
+/// let (_, errors) = parse_all_recovering::<ast::DeclStruct>("struct Foo { a, !, c }");
+/// assert_eq!(errors.len(), 1);
 /// ```
 impl Parse for DeclStruct {
     fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        let mut attributes = Vec::new();
+
+        while parser.peek::<ast::Attribute>()? {
+            attributes.push(parser.parse()?);
+        }
+
+        let visibility = parser.parse()?;
+        let struct_ = parser.parse()?;
+        let ident = parser.parse()?;
+
+        let generics = if parser.peek::<ast::Generics>()? {
+            Some(parser.parse()?)
+        } else {
+            None
+        };
+
         Ok(Self {
-            struct_: parser.parse()?,
-            ident: parser.parse()?,
+            attributes,
+            visibility,
+            struct_,
+            ident,
+            generics,
             body: parser.parse()?,
         })
     }
@@ -108,13 +157,69 @@ impl Parse for EmptyBody {
     }
 }
 
+/// A single field of a [`TupleBody`] or [`StructBody`], with its own
+/// visibility and attributes.
+#[derive(Debug, Clone)]
+pub struct StructField {
+    /// Outer attributes and doc comments on the field.
+    pub attributes: Vec<ast::Attribute>,
+    /// The visibility of the field.
+    pub visibility: ast::Visibility,
+    /// The identifier of the field.
+    pub ident: ast::Ident,
+}
+
+impl StructField {
+    /// Get the span for the field, covering its attributes.
+    pub fn span(&self) -> Span {
+        let mut start = self.ident.span();
+
+        if let Some(visibility_span) = self.visibility.span() {
+            start = visibility_span.join(start);
+        }
+
+        if let Some(attribute) = self.attributes.first() {
+            start = attribute.span().join(start);
+        }
+
+        start
+    }
+}
+
+/// Parse implementation for a struct field.
+///
+/// # Examples
+///
+/// ```rust
+/// use rune::{parse_all, ast};
+///
+/// parse_all::<ast::StructField>("a").unwrap();
+/// parse_all::<ast::StructField>("pub a").unwrap();
+/// parse_all::<ast::StructField>("#[serde(default)] pub a").unwrap();
+/// ```
+impl Parse for StructField {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        let mut attributes = Vec::new();
+
+        while parser.peek::<ast::Attribute>()? {
+            attributes.push(parser.parse()?);
+        }
+
+        Ok(Self {
+            attributes,
+            visibility: parser.parse()?,
+            ident: parser.parse()?,
+        })
+    }
+}
+
 /// A variant declaration.
 #[derive(Debug, Clone)]
 pub struct TupleBody {
     /// The opening paren.
     pub open: ast::OpenParen,
     /// Fields in the variant.
-    pub fields: Vec<(ast::Ident, Option<ast::Comma>)>,
+    pub fields: Vec<(StructField, Option<ast::Comma>)>,
     /// The close paren.
     pub close: ast::CloseParen,
 }
@@ -142,7 +247,25 @@ impl Parse for TupleBody {
         let mut fields = Vec::new();
 
         while !parser.peek::<ast::CloseParen>()? {
-            let field = parser.parse()?;
+            let field = match parser.parse() {
+                Ok(field) => field,
+                Err(error) => {
+                    // Don't let one malformed field abort the whole
+                    // declaration; recover at the next field or the closing
+                    // paren and keep collecting diagnostics. The malformed
+                    // field itself is omitted from `fields` rather than
+                    // replaced with a placeholder.
+                    parser.report_error(error);
+                    parser.recover_until(&[Kind::Comma, Kind::Close(Delimiter::Parenthesis)])?;
+
+                    if parser.peek::<ast::Comma>()? {
+                        parser.parse::<ast::Comma>()?;
+                        continue;
+                    }
+
+                    break;
+                }
+            };
 
             let comma = if parser.peek::<ast::Comma>()? {
                 Some(parser.parse()?)
@@ -175,7 +298,7 @@ pub struct StructBody {
     /// The opening brace.
     pub open: ast::OpenBrace,
     /// Fields in the variant.
-    pub fields: Vec<(ast::Ident, Option<ast::Comma>)>,
+    pub fields: Vec<(StructField, Option<ast::Comma>)>,
     /// The close brace.
     pub close: ast::CloseBrace,
 }
@@ -203,7 +326,25 @@ impl Parse for StructBody {
         let mut fields = Vec::new();
 
         while !parser.peek::<ast::CloseBrace>()? {
-            let field = parser.parse()?;
+            let field = match parser.parse() {
+                Ok(field) => field,
+                Err(error) => {
+                    // Don't let one malformed field abort the whole
+                    // declaration; recover at the next field or the closing
+                    // brace and keep collecting diagnostics. The malformed
+                    // field itself is omitted from `fields` rather than
+                    // replaced with a placeholder.
+                    parser.report_error(error);
+                    parser.recover_until(&[Kind::Comma, Kind::Close(Delimiter::Brace)])?;
+
+                    if parser.peek::<ast::Comma>()? {
+                        parser.parse::<ast::Comma>()?;
+                        continue;
+                    }
+
+                    break;
+                }
+            };
 
             let comma = if parser.peek::<ast::Comma>()? {
                 Some(parser.parse()?)
@@ -229,3 +370,69 @@ impl Parse for StructBody {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{ast, parse_all, parse_all_recovering};
+
+    #[test]
+    fn threads_visibility_generics_and_attributes() {
+        let decl =
+            parse_all::<ast::DeclStruct>("#[derive(Debug)] pub struct Foo<T, U> { a, b }").unwrap();
+
+        assert_eq!(decl.attributes.len(), 1);
+        assert!(decl.visibility.span().is_some());
+        assert_eq!(decl.generics.unwrap().parameters.len(), 2);
+    }
+
+    #[test]
+    fn needs_semi_colon_only_for_an_empty_body() {
+        let empty = parse_all::<ast::DeclStruct>("struct Foo").unwrap();
+        assert!(empty.needs_semi_colon());
+
+        let tuple = parse_all::<ast::DeclStruct>("struct Foo(a, b)").unwrap();
+        assert!(!tuple.needs_semi_colon());
+
+        let struct_body = parse_all::<ast::DeclStruct>("struct Foo { a, b }").unwrap();
+        assert!(!struct_body.needs_semi_colon());
+    }
+
+    #[test]
+    fn span_covers_a_leading_attribute() {
+        let decl = parse_all::<ast::DeclStruct>("#[foo] struct Bar").unwrap();
+
+        // The `#[foo]` attribute sits before the `struct` keyword, so the
+        // declaration's span must start at the attribute, not at `struct_`.
+        assert_eq!(decl.span().start, 0);
+        assert!(decl.struct_.span().start > decl.span().start);
+    }
+
+    #[test]
+    fn recovers_past_a_single_malformed_field() {
+        let (result, errors) = parse_all_recovering::<ast::DeclStruct>("struct Foo { a, !, c }");
+
+        assert!(result.is_ok());
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn recovers_past_multiple_malformed_fields() {
+        let (result, errors) =
+            parse_all_recovering::<ast::DeclStruct>("struct Foo { a, !, b, !, c }");
+
+        assert!(result.is_ok());
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn recovery_does_not_stop_inside_a_nested_group() {
+        // The `,` inside `!(1, 2)` sits at nesting depth 1, so recovery
+        // must not treat it as the synchronization point - only the `,`
+        // right after the closing paren is at depth 0.
+        let (result, errors) =
+            parse_all_recovering::<ast::DeclStruct>("struct Foo { a, !(1, 2), c }");
+
+        assert!(result.is_ok());
+        assert_eq!(errors.len(), 1);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::ast;
+use crate::error::ParseError;
+use crate::parser::Parser;
+use crate::traits::{Parse, Peek};
+use runestick::Span;
+
+/// Generic parameters for a declaration, e.g. `<T, U>`.
+///
+/// # Examples
+///
+/// ```rust
+/// use rune::{parse_all, ast};
+///
+/// parse_all::<ast::Generics>("<T>").unwrap();
+/// parse_all::<ast::Generics>("<T, U>").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Generics {
+    /// The opening `<`.
+    pub open: ast::Lt,
+    /// The generic parameters.
+    pub parameters: Vec<(ast::Ident, Option<ast::Comma>)>,
+    /// The closing `>`.
+    pub close: ast::Gt,
+}
+
+impl Generics {
+    /// Get the span for the generic parameters.
+    pub fn span(&self) -> Span {
+        self.open.span().join(self.close.span())
+    }
+}
+
+impl Peek for Generics {
+    fn peek(t1: Option<ast::Token>, _t2: Option<ast::Token>) -> bool {
+        matches!(t1.map(|t| t.kind), Some(ast::Kind::Lt))
+    }
+}
+
+impl Parse for Generics {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        let open = parser.parse()?;
+
+        let mut parameters = Vec::new();
+
+        while !parser.peek::<ast::Gt>()? {
+            let parameter = parser.parse()?;
+
+            let comma = if parser.peek::<ast::Comma>()? {
+                Some(parser.parse()?)
+            } else {
+                None
+            };
+
+            let done = comma.is_none();
+
+            parameters.push((parameter, comma));
+
+            if done {
+                break;
+            }
+        }
+
+        let close = parser.parse()?;
+
+        Ok(Self {
+            open,
+            parameters,
+            close,
+        })
+    }
+}
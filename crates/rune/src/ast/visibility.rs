@@ -0,0 +1,44 @@
+use crate::ast;
+use crate::error::ParseError;
+use crate::parser::Parser;
+use crate::traits::Parse;
+use runestick::Span;
+
+/// The visibility of a declaration or field.
+///
+/// # Examples
+///
+/// ```rust
+/// use rune::{parse_all, ast};
+///
+/// parse_all::<ast::Visibility>("").unwrap();
+/// parse_all::<ast::Visibility>("pub").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub enum Visibility {
+    /// Visible only within the current module.
+    Inherited,
+    /// Declared `pub`.
+    Public(ast::Pub),
+}
+
+impl Visibility {
+    /// Get the span of the visibility, if it's anything other than
+    /// [`Visibility::Inherited`].
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::Inherited => None,
+            Self::Public(pub_) => Some(pub_.span()),
+        }
+    }
+}
+
+impl Parse for Visibility {
+    fn parse(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        if parser.peek::<ast::Pub>()? {
+            return Ok(Self::Public(parser.parse()?));
+        }
+
+        Ok(Self::Inherited)
+    }
+}
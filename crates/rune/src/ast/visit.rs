@@ -0,0 +1,306 @@
+//! A visitor/folder framework over the `ast` tree, so passes can walk or
+//! rewrite nodes without hand-matching every variant themselves.
+//!
+//! [`Visitor`] walks the tree read-only, with default-recursing `visit_*`
+//! methods a pass only overrides for the node kinds it cares about.
+//! [`Folder`] does the same but reconstructs the tree, letting a pass
+//! rewrite specific node kinds in place. Both mirror the walker/folder
+//! pairs generated by proc-macros in other Rust parser front-ends, just
+//! hand-written since this crate doesn't depend on one.
+
+use crate::ast;
+
+/// Read-only, default-recursing traversal of the AST.
+pub trait Visitor {
+    /// Visit a struct declaration.
+    fn visit_decl_struct(&mut self, node: &ast::DeclStruct) {
+        walk_decl_struct(self, node);
+    }
+
+    /// Visit a struct body.
+    fn visit_decl_struct_body(&mut self, node: &ast::DeclStructBody) {
+        walk_decl_struct_body(self, node);
+    }
+
+    /// Visit a single struct field.
+    fn visit_struct_field(&mut self, node: &ast::StructField) {
+        walk_struct_field(self, node);
+    }
+
+    /// Visit an attribute. Attributes have no further children to recurse
+    /// into, so there's no corresponding `walk_attribute`.
+    fn visit_attribute(&mut self, _node: &ast::Attribute) {}
+
+    /// Visit an `.await` expression.
+    fn visit_expr_await(&mut self, node: &ast::ExprAwait) {
+        walk_expr_await(self, node);
+    }
+
+    /// Visit an index-set expression, e.g. `a[b] = c`.
+    fn visit_expr_index_set(&mut self, node: &ast::ExprIndexSet) {
+        walk_expr_index_set(self, node);
+    }
+
+    /// Visit an arbitrary expression.
+    ///
+    /// The default implementation does nothing. Since the `Expr` enum has
+    /// many more variants than this module has visibility into, a pass that
+    /// needs to recurse into expressions should match on `node` and dispatch
+    /// to the relevant `visit_expr_*` method itself.
+    fn visit_expr(&mut self, _node: &ast::Expr) {}
+}
+
+/// Walk the children of a [`ast::DeclStruct`].
+pub fn walk_decl_struct<V>(visitor: &mut V, node: &ast::DeclStruct)
+where
+    V: Visitor + ?Sized,
+{
+    for attribute in &node.attributes {
+        visitor.visit_attribute(attribute);
+    }
+
+    visitor.visit_decl_struct_body(&node.body);
+}
+
+/// Walk the children of a [`ast::DeclStructBody`].
+pub fn walk_decl_struct_body<V>(visitor: &mut V, node: &ast::DeclStructBody)
+where
+    V: Visitor + ?Sized,
+{
+    match node {
+        ast::DeclStructBody::EmptyBody(..) => (),
+        ast::DeclStructBody::TupleBody(body) => {
+            for (field, _) in &body.fields {
+                visitor.visit_struct_field(field);
+            }
+        }
+        ast::DeclStructBody::StructBody(body) => {
+            for (field, _) in &body.fields {
+                visitor.visit_struct_field(field);
+            }
+        }
+    }
+}
+
+/// Walk the children of a [`ast::StructField`].
+pub fn walk_struct_field<V>(visitor: &mut V, node: &ast::StructField)
+where
+    V: Visitor + ?Sized,
+{
+    for attribute in &node.attributes {
+        visitor.visit_attribute(attribute);
+    }
+}
+
+/// Walk the children of a [`ast::ExprAwait`].
+pub fn walk_expr_await<V>(visitor: &mut V, node: &ast::ExprAwait)
+where
+    V: Visitor + ?Sized,
+{
+    visitor.visit_expr(&node.expr);
+}
+
+/// Walk the children of a [`ast::ExprIndexSet`].
+pub fn walk_expr_index_set<V>(visitor: &mut V, node: &ast::ExprIndexSet)
+where
+    V: Visitor + ?Sized,
+{
+    visitor.visit_expr(&node.value);
+    visitor.visit_expr(&node.index);
+    visitor.visit_expr(&node.target);
+}
+
+/// Rebuilds the AST, letting a pass rewrite specific node kinds without
+/// reconstructing every variant by hand.
+pub trait Folder {
+    /// Fold a struct declaration.
+    fn fold_decl_struct(&mut self, node: ast::DeclStruct) -> ast::DeclStruct {
+        let attributes = node
+            .attributes
+            .into_iter()
+            .map(|attribute| self.fold_attribute(attribute))
+            .collect();
+        let body = self.fold_decl_struct_body(node.body);
+
+        ast::DeclStruct {
+            attributes,
+            visibility: node.visibility,
+            struct_: node.struct_,
+            ident: node.ident,
+            generics: node.generics,
+            body,
+        }
+    }
+
+    /// Fold a struct body.
+    fn fold_decl_struct_body(&mut self, node: ast::DeclStructBody) -> ast::DeclStructBody {
+        match node {
+            ast::DeclStructBody::EmptyBody(body) => ast::DeclStructBody::EmptyBody(body),
+            ast::DeclStructBody::TupleBody(mut body) => {
+                body.fields = body
+                    .fields
+                    .into_iter()
+                    .map(|(field, comma)| (self.fold_struct_field(field), comma))
+                    .collect();
+                ast::DeclStructBody::TupleBody(body)
+            }
+            ast::DeclStructBody::StructBody(mut body) => {
+                body.fields = body
+                    .fields
+                    .into_iter()
+                    .map(|(field, comma)| (self.fold_struct_field(field), comma))
+                    .collect();
+                ast::DeclStructBody::StructBody(body)
+            }
+        }
+    }
+
+    /// Fold a single struct field.
+    fn fold_struct_field(&mut self, mut node: ast::StructField) -> ast::StructField {
+        node.attributes = node
+            .attributes
+            .into_iter()
+            .map(|attribute| self.fold_attribute(attribute))
+            .collect();
+        node
+    }
+
+    /// Fold an attribute. The default implementation leaves it unchanged.
+    fn fold_attribute(&mut self, node: ast::Attribute) -> ast::Attribute {
+        node
+    }
+
+    /// Fold an `.await` expression.
+    fn fold_expr_await(&mut self, mut node: ast::ExprAwait) -> ast::ExprAwait {
+        node.expr = Box::new(self.fold_expr(*node.expr));
+        node
+    }
+
+    /// Fold an index-set expression, e.g. `a[b] = c`.
+    fn fold_expr_index_set(&mut self, mut node: ast::ExprIndexSet) -> ast::ExprIndexSet {
+        node.value = Box::new(self.fold_expr(*node.value));
+        node.index = Box::new(self.fold_expr(*node.index));
+        node.target = Box::new(self.fold_expr(*node.target));
+        node
+    }
+
+    /// Fold an arbitrary expression. The default implementation leaves it
+    /// unchanged; see [`Visitor::visit_expr`] for why this doesn't recurse
+    /// further on its own.
+    fn fold_expr(&mut self, node: ast::Expr) -> ast::Expr {
+        node
+    }
+}
+
+/// Replace every `Span { start: N, end: M }` substring produced by `Span`'s
+/// derived `Debug` impl with `Span { .. }`, so two `{:#?}` dumps of AST
+/// nodes can be compared while ignoring source positions.
+///
+/// This only scrubs the standalone identifier `Span` (checking the byte
+/// right before the match isn't itself an identifier character), not just
+/// any `...Span {` suffix - otherwise a future type whose pretty-`Debug`
+/// name happens to end in `Span` (e.g. `TokenSpan`) would have its real
+/// fields silently erased instead of just its span. A fully structural
+/// [`Folder`] over `Span` fields would be more robust still, but the leaf
+/// token types that actually carry a `Span` (`Ident`, `Pub`, delimiters,
+/// ...) aren't defined in this module, so there's nothing here to fold
+/// into.
+pub fn normalize_spans(debug: &str) -> String {
+    const NEEDLE: &str = "Span {";
+
+    let mut out = String::with_capacity(debug.len());
+    let mut rest = debug;
+
+    while let Some(found) = rest.find(NEEDLE) {
+        let preceded_by_ident_char = rest[..found]
+            .chars()
+            .next_back()
+            .or_else(|| out.chars().next_back())
+            .map_or(false, |c| c.is_alphanumeric() || c == '_');
+
+        if preceded_by_ident_char {
+            out.push_str(&rest[..found + NEEDLE.len()]);
+            rest = &rest[found + NEEDLE.len()..];
+            continue;
+        }
+
+        out.push_str(&rest[..found]);
+
+        match rest[found..].find('}') {
+            Some(end) => {
+                out.push_str("Span { .. }");
+                rest = &rest[found + end + 1..];
+            }
+            None => {
+                out.push_str(&rest[found..]);
+                rest = "";
+                break;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Assert that two AST nodes are structurally equal, ignoring every `Span`
+/// (and therefore every source position) they carry.
+///
+/// This lets parser round-trip and desugaring tests compare shape without
+/// tripping over byte offsets, so they don't need a hand-written
+/// span-insensitive `PartialEq` for every node kind.
+///
+/// # Examples
+///
+/// ```rust
+/// use rune::{assert_ast_eq_ignore_span, parse_all, ast};
+///
+/// let a = parse_all::<ast::DeclStruct>("struct Foo { a, b }").unwrap();
+/// let b = parse_all::<ast::DeclStruct>("struct   Foo   {   a,   b   }").unwrap();
+/// assert_ast_eq_ignore_span!(a, b);
+/// ```
+#[macro_export]
+macro_rules! assert_ast_eq_ignore_span {
+    ($left:expr, $right:expr $(,)?) => {{
+        let left = $crate::ast::visit::normalize_spans(&format!("{:#?}", $left));
+        let right = $crate::ast::visit::normalize_spans(&format!("{:#?}", $right));
+
+        if left != right {
+            panic!(
+                "assertion failed: `(left == right)` (ignoring spans)\n  left: {}\n right: {}",
+                left, right
+            );
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_spans;
+
+    #[test]
+    fn scrubs_a_span_field() {
+        let debug = "Ident {\n    span: Span {\n        start: 0,\n        end: 3,\n    },\n}";
+
+        assert_eq!(
+            normalize_spans(debug),
+            "Ident {\n    span: Span { .. },\n}"
+        );
+    }
+
+    #[test]
+    fn leaves_a_type_whose_name_merely_ends_in_span_alone() {
+        // `TokenSpan { value: 1 }` must not be mistaken for a `Span` just
+        // because its pretty-printed name ends with the substring "Span {".
+        let debug = "TokenSpan {\n    value: 1,\n}";
+
+        assert_eq!(normalize_spans(debug), debug);
+    }
+
+    #[test]
+    fn scrubs_a_real_span_immediately_preceded_by_a_non_ident_char() {
+        let debug = "(Span {\n    start: 0,\n    end: 1,\n})";
+
+        assert_eq!(normalize_spans(debug), "(Span { .. })");
+    }
+}
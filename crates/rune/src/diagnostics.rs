@@ -0,0 +1,264 @@
+//! Rich, span-underlining diagnostic rendering for compile and parse errors.
+//!
+//! This turns `Span`-carrying errors into annotated terminal output instead
+//! of bare `Debug` strings: the offending source lines are printed with
+//! caret/underline markers beneath the exact byte range, alongside a
+//! primary message and optional secondary notes and help text.
+
+use crate::error::ParseError;
+use runestick::{Span, UnitError};
+use std::fmt;
+
+/// A single labeled span within a [`Report`], pointing at a piece of source
+/// with a message explaining why it's relevant.
+#[derive(Debug, Clone)]
+pub struct Label {
+    /// The span being pointed at.
+    pub span: Span,
+    /// The message to print underneath it.
+    pub message: String,
+}
+
+impl Label {
+    /// Construct a new label.
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// A compiler-grade diagnostic: a primary message, one or more labeled
+/// spans into the source, and optional secondary notes and help text.
+///
+/// # Examples
+///
+/// ```rust
+/// use rune::diagnostics::Report;
+/// use runestick::Span;
+///
+/// let report = Report::new("duplicate label `foo_0`")
+///     .with_label(Span::new(0, 3), "first defined here")
+///     .with_label(Span::new(10, 13), "duplicate defined here");
+///
+/// let mut out = String::new();
+/// report.render("foo 1\nfoo 2\n", &mut out, false).unwrap();
+/// assert!(out.contains("duplicate label"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// The primary message of the report.
+    pub message: String,
+    /// Labeled spans called out in the report.
+    pub labels: Vec<Label>,
+    /// Secondary notes, printed after the labeled spans.
+    pub notes: Vec<String>,
+    /// An optional concluding help message.
+    pub help: Option<String>,
+}
+
+impl Report {
+    /// Construct a new report with just a primary message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    /// Add a labeled span to the report.
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label::new(span, message));
+        self
+    }
+
+    /// Add a secondary note to the report.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Set the concluding help message of the report.
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Render this report against `source`.
+    ///
+    /// Set `color` to `true` to highlight the underline markers with ANSI
+    /// escapes; embedders that don't control their output stream (e.g. when
+    /// writing to a file) should leave it `false`.
+    pub fn render(&self, source: &str, out: &mut impl fmt::Write, color: bool) -> fmt::Result {
+        writeln!(out, "error: {}", self.message)?;
+
+        for label in &self.labels {
+            render_label(source, label, out, color)?;
+        }
+
+        for note in &self.notes {
+            writeln!(out, "note: {}", note)?;
+        }
+
+        if let Some(help) = &self.help {
+            writeln!(out, "help: {}", help)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Render a single labeled span, underlining every source line it covers.
+fn render_label(source: &str, label: &Label, out: &mut impl fmt::Write, color: bool) -> fmt::Result {
+    let start = locate(source, label.span.start);
+    let end = locate(source, label.span.end);
+
+    writeln!(out, "  --> line {}:{}", start.line, start.column)?;
+
+    for (line_number, line_text) in lines(source, start.line, end.line) {
+        writeln!(out, "{:>4} | {}", line_number, line_text)?;
+
+        let underline_start = if line_number == start.line {
+            start.column
+        } else {
+            1
+        };
+
+        let underline_end = if line_number == end.line {
+            end.column.max(underline_start + 1)
+        } else {
+            line_text.chars().count() + 1
+        };
+
+        let mut underline = String::new();
+        underline.push_str(&" ".repeat(underline_start.saturating_sub(1)));
+        underline.push_str(&"^".repeat(underline_end - underline_start));
+
+        if color {
+            writeln!(out, "     | \x1b[31m{}\x1b[0m {}", underline, label.message)?;
+        } else {
+            writeln!(out, "     | {} {}", underline, label.message)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A 1-indexed line/column position.
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+/// Translate a byte offset into a 1-indexed line/column position.
+fn locate(source: &str, offset: usize) -> Position {
+    let mut line = 1;
+    let mut column = 1;
+
+    for (index, c) in source.char_indices() {
+        if index >= offset {
+            break;
+        }
+
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+
+    Position { line, column }
+}
+
+/// Iterate over the source lines numbered `start_line..=end_line`.
+fn lines(source: &str, start_line: usize, end_line: usize) -> impl Iterator<Item = (usize, &str)> {
+    source
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(move |(line_number, _)| *line_number >= start_line && *line_number <= end_line)
+}
+
+/// Implemented by errors that carry enough span information to be turned
+/// into a [`Report`].
+pub trait Diagnostic {
+    /// Build a report describing this error.
+    fn report(&self) -> Report;
+}
+
+impl Diagnostic for ParseError {
+    fn report(&self) -> Report {
+        Report::new(self.to_string()).with_label(self.span(), "here")
+    }
+}
+
+impl Diagnostic for UnitError {
+    fn report(&self) -> Report {
+        match self {
+            // Ideally this would `.with_label` both the original and the
+            // duplicate definition site, but `UnitError::DuplicateLabel`
+            // only carries the `Label` itself, not the spans of either
+            // definition, so there's nothing to point at yet.
+            UnitError::DuplicateLabel { label } => Report::new(format!("duplicate label `{}`", label))
+                .with_note(
+                    "original and duplicate definition sites can't be shown here; \
+                     `UnitError::DuplicateLabel` doesn't carry their spans",
+                ),
+            error => Report::new(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_tracks_line_and_column_across_newlines() {
+        let source = "abc\ndef\nghi";
+
+        let start = locate(source, 0);
+        assert_eq!((start.line, start.column), (1, 1));
+
+        // 'd' is the first character of the second line.
+        let second_line = locate(source, 4);
+        assert_eq!((second_line.line, second_line.column), (2, 1));
+
+        // 'h' is the second character of the third line.
+        let third_line = locate(source, 9);
+        assert_eq!((third_line.line, third_line.column), (3, 2));
+    }
+
+    #[test]
+    fn render_underlines_a_single_line_span() {
+        let report = Report::new("unexpected token").with_label(Span::new(1, 3), "here");
+
+        let mut out = String::new();
+        report.render("ab+cd\n", &mut out, false).unwrap();
+
+        assert!(out.contains("error: unexpected token"));
+        assert!(out.contains("ab+cd"));
+        assert!(out.contains("^^ here"));
+    }
+
+    #[test]
+    fn render_supports_multiple_labels_notes_and_help() {
+        let report = Report::new("duplicate label `foo_0`")
+            .with_label(Span::new(0, 3), "first defined here")
+            .with_label(Span::new(8, 11), "duplicate defined here")
+            .with_note("labels must be unique within a function")
+            .with_help("rename one of them");
+
+        let mut out = String::new();
+        report.render("foo: a\nfoo: b\n", &mut out, false).unwrap();
+
+        assert!(out.contains("first defined here"));
+        assert!(out.contains("duplicate defined here"));
+        assert!(out.contains("note: labels must be unique within a function"));
+        assert!(out.contains("help: rename one of them"));
+    }
+}
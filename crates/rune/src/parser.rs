@@ -0,0 +1,63 @@
+use crate::ast::{Delimiter, Kind};
+use crate::error::ParseError;
+use crate::traits::Parse;
+
+// `Parser` itself is declared next to its token-stream plumbing, outside
+// this file. Recovery needs an `errors: Vec<ParseError>` field there,
+// zero-initialized in `Parser::new`, for `report_error`/`parse_all_recovering`
+// below to compile.
+impl<'a> Parser<'a> {
+    /// Consume and discard tokens until one of `synchronize` is reached, or
+    /// the end of the token stream.
+    ///
+    /// Nesting of `Open`/`Close` delimiters is tracked so that a `,` or
+    /// closing delimiter *inside* a balanced group (e.g. a nested tuple or
+    /// block) doesn't stop recovery early; only a synchronization token seen
+    /// at the same nesting depth we started at counts.
+    pub(crate) fn recover_until(&mut self, synchronize: &[Kind]) -> Result<(), ParseError> {
+        let mut depth = 0usize;
+
+        while let Some(token) = self.token_peek()? {
+            if depth == 0 && synchronize.contains(&token.kind) {
+                return Ok(());
+            }
+
+            match token.kind {
+                Kind::Open(..) => depth += 1,
+                Kind::Close(..) if depth > 0 => depth -= 1,
+                _ => (),
+            }
+
+            self.token_next()?;
+        }
+
+        Ok(())
+    }
+
+    /// Record a diagnostic without aborting parsing, for use by recovering
+    /// parse implementations. Collected errors are drained by
+    /// [`parse_all_recovering`].
+    pub(crate) fn report_error(&mut self, error: ParseError) {
+        self.errors.push(error);
+    }
+}
+
+/// Parse `source` into a `T`, recovering from individual parse errors
+/// instead of bailing on the first one.
+///
+/// Recovery only happens *within* constructs whose `Parse` impl opts into it
+/// (today, `TupleBody`/`StructBody` field lists): a malformed field is
+/// reported and skipped rather than aborting the whole declaration. This
+/// does not yet produce a best-effort `T` on its own - if parsing runs out
+/// of input or hits an error outside a recovering construct, the first
+/// element of the tuple is still `Err` and carries no partial tree, only the
+/// diagnostics collected up to that point are available via the second
+/// element.
+pub fn parse_all_recovering<T>(source: &str) -> (Result<T, ParseError>, Vec<ParseError>)
+where
+    T: Parse,
+{
+    let mut parser = Parser::new(source);
+    let result = parser.parse::<T>();
+    (result, parser.errors)
+}
@@ -2,6 +2,7 @@
 
 use crate::collections::HashMap;
 use crate::{Hash, Inst, Span, UnitError};
+use std::collections::HashSet;
 use std::fmt;
 
 /// A label that can be jumped to.
@@ -139,4 +140,568 @@ impl Assembly {
 
         self.push(raw, span);
     }
+
+    /// Run an optimization pass over the assembly, threading jumps,
+    /// eliminating unreachable instructions, and collapsing common
+    /// branch/jump idioms into cheaper single instructions.
+    ///
+    /// This must be run before labels are resolved to offsets when lowering
+    /// to a [`Unit`]. It's opt-in: callers that want a 1:1 mapping between
+    /// source spans and emitted instructions (for example debug builds) can
+    /// simply not call it.
+    ///
+    /// [`Unit`]: crate::Unit
+    pub fn optimize(&mut self) {
+        self.thread_jumps();
+        self.eliminate_dead_code();
+        self.peephole();
+    }
+
+    /// Rewrite every unconditional jump to target the final destination of
+    /// the chain of unconditional jumps it lands on, so that `a -> b -> c`
+    /// collapses to `a -> c`.
+    fn thread_jumps(&mut self) {
+        for index in 0..self.instructions.len() {
+            let label = match self.instructions[index].0 {
+                AssemblyInst::Jump { label } => label,
+                _ => continue,
+            };
+
+            let mut visited = HashSet::new();
+            visited.insert(label);
+
+            let mut target = label;
+
+            loop {
+                let offset = match self.labels.get(&target) {
+                    Some(offset) => *offset,
+                    None => break,
+                };
+
+                let next = match self.instructions.get(offset) {
+                    Some((AssemblyInst::Jump { label: next }, _)) => *next,
+                    _ => break,
+                };
+
+                if !visited.insert(next) {
+                    // We've found a cycle of jumps-to-jumps. Leave the last
+                    // known-good target in place rather than looping forever.
+                    break;
+                }
+
+                target = next;
+            }
+
+            self.instructions[index].0 = AssemblyInst::Jump { label: target };
+        }
+    }
+
+    /// Delete instructions that immediately follow an unconditional jump or a
+    /// return, up until the next instruction that's targeted by a label,
+    /// since nothing can reach them.
+    fn eliminate_dead_code(&mut self) {
+        let mut remove = HashSet::new();
+        let mut index = 0;
+
+        while index < self.instructions.len() {
+            let terminator = matches!(
+                self.instructions[index].0,
+                AssemblyInst::Jump { .. } | AssemblyInst::Raw { raw: Inst::Return }
+            );
+
+            if !terminator {
+                index += 1;
+                continue;
+            }
+
+            let mut unreachable = index + 1;
+
+            while unreachable < self.instructions.len()
+                && !self.labels_rev.contains_key(&unreachable)
+            {
+                remove.insert(unreachable);
+                unreachable += 1;
+            }
+
+            index = unreachable;
+        }
+
+        self.remove_instructions(remove);
+    }
+
+    /// Collapse common branch idioms:
+    ///
+    /// * `JumpIf L; Jump M` where `L` targets the instruction right after the
+    ///   `Jump` becomes a single `JumpIfNot M`. This is skipped if the `Jump`
+    ///   is itself labeled, since some other instruction may be jumping
+    ///   straight to it, bypassing the `JumpIf`.
+    /// * A `Jump` that targets the very next instruction is a no-op and is
+    ///   dropped.
+    fn peephole(&mut self) {
+        let mut remove = HashSet::new();
+        let mut index = 0;
+
+        while index < self.instructions.len() {
+            if let (AssemblyInst::JumpIf { label }, Some((AssemblyInst::Jump { label: to }, _))) = (
+                self.instructions[index].0.clone(),
+                self.instructions.get(index + 1).cloned(),
+            ) {
+                if self.labels.get(&label) == Some(&(index + 2))
+                    && !self.labels_rev.contains_key(&(index + 1))
+                {
+                    self.instructions[index].0 = AssemblyInst::JumpIfNot { label: to };
+                    remove.insert(index + 1);
+                    index += 2;
+                    continue;
+                }
+            }
+
+            if let AssemblyInst::Jump { label } = self.instructions[index].0 {
+                if self.labels.get(&label) == Some(&(index + 1)) {
+                    remove.insert(index);
+                }
+            }
+
+            index += 1;
+        }
+
+        self.remove_instructions(remove);
+    }
+
+    /// Remove the instructions at the given offsets, renumbering every label
+    /// and dropping comments that were attached to a removed position.
+    fn remove_instructions(&mut self, remove: HashSet<usize>) {
+        if remove.is_empty() {
+            return;
+        }
+
+        let old_len = self.instructions.len();
+
+        // `offset_map[old_offset]` is the offset `old_offset` maps to after
+        // removal. The entry at `old_len` acts as a sentinel for labels that
+        // point to the (non-existent) instruction just past the end.
+        let mut offset_map = Vec::with_capacity(old_len + 1);
+        let mut new_offset = 0;
+
+        for old_offset in 0..old_len {
+            offset_map.push(new_offset);
+
+            if !remove.contains(&old_offset) {
+                new_offset += 1;
+            }
+        }
+
+        offset_map.push(new_offset);
+
+        let mut instructions = Vec::with_capacity(new_offset);
+        let mut comments = HashMap::default();
+
+        for (old_offset, entry) in self.instructions.drain(..).enumerate() {
+            if remove.contains(&old_offset) {
+                continue;
+            }
+
+            if let Some(comment) = self.comments.remove(&old_offset) {
+                comments.insert(instructions.len(), comment);
+            }
+
+            instructions.push(entry);
+        }
+
+        self.instructions = instructions;
+        self.comments = comments;
+
+        self.labels = self
+            .labels
+            .drain()
+            .map(|(label, offset)| (label, offset_map[offset]))
+            .collect();
+
+        self.labels_rev = self
+            .labels
+            .iter()
+            .map(|(&label, &offset)| (offset, label))
+            .collect();
+    }
+
+    /// Render this assembly as a stable, human-readable textual form.
+    ///
+    /// Every label from [`labels_rev`][Self::labels_rev] is emitted as a
+    /// `label_n:` line, each instruction is printed on its own line with
+    /// jump targets resolved to their label names, `Inst::Call` is rendered
+    /// as `call 0x{hash:016x}`, and every distinct hash in
+    /// [`required_functions`][Self::required_functions] is listed as an
+    /// `extern` declaration up front. Comments attached through
+    /// [`push_with_comment`][Self::push_with_comment] are appended as
+    /// trailing `; ...` annotations.
+    ///
+    /// The result can be parsed back into an [`Assembly`] with [`assemble`]
+    /// as long as it only holds the instruction forms that `assemble`
+    /// understands, which makes this a useful, diffable artifact for snapshot-testing
+    /// codegen instead of asserting on VM execution.
+    pub fn disassemble(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        let mut externs: Vec<String> = self
+            .required_functions
+            .keys()
+            .map(|hash| format!("{:016x}", hash))
+            .collect();
+
+        externs.sort();
+        externs.dedup();
+
+        for hash in &externs {
+            writeln!(out, "extern 0x{};", hash)?;
+        }
+
+        if !externs.is_empty() {
+            writeln!(out)?;
+        }
+
+        for (offset, (inst, _span)) in self.instructions.iter().enumerate() {
+            if let Some(label) = self.labels_rev.get(&offset) {
+                writeln!(out, "{}:", label)?;
+            }
+
+            write!(out, "    ")?;
+
+            match inst {
+                AssemblyInst::Jump { label } => write!(out, "jump {}", label)?,
+                AssemblyInst::JumpIf { label } => write!(out, "jump_if {}", label)?,
+                AssemblyInst::JumpIfNot { label } => write!(out, "jump_if_not {}", label)?,
+                AssemblyInst::JumpIfBranch { branch, label } => {
+                    write!(out, "jump_if_branch {} {}", branch, label)?
+                }
+                AssemblyInst::PopAndJumpIf { count, label } => {
+                    write!(out, "pop_and_jump_if {} {}", count, label)?
+                }
+                AssemblyInst::PopAndJumpIfNot { count, label } => {
+                    write!(out, "pop_and_jump_if_not {} {}", count, label)?
+                }
+                AssemblyInst::Raw {
+                    raw: Inst::Call { hash, .. },
+                } => write!(out, "call 0x{:016x}", hash)?,
+                AssemblyInst::Raw { raw } => write!(out, "{:?}", raw)?,
+            }
+
+            if let Some(comments) = self.comments.get(&offset) {
+                for comment in comments {
+                    write!(out, " ; {}", comment)?;
+                }
+            }
+
+            writeln!(out)?;
+        }
+
+        // A label pointing at the (non-existent) instruction just past the
+        // end, e.g. an end-of-function marker.
+        if let Some(label) = self.labels_rev.get(&self.instructions.len()) {
+            writeln!(out, "{}:", label)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the textual form produced by [`Assembly::disassemble`] back into an
+/// [`Assembly`].
+///
+/// This only understands the instruction forms `disassemble` itself emits
+/// structurally: labels, externs, the jump family, calls, and `Return`;
+/// any other raw instruction line is an error. It exists so
+/// tests can assert on exact codegen by round-tripping a snapshot rather
+/// than driving the VM, not as a general-purpose assembler for the full
+/// instruction set.
+///
+/// This is only available to this crate's own tests: labels parsed from an
+/// unrecognized name leak their backing string to satisfy `new_label`'s
+/// `&'static str` requirement (see [`label_named`]), which is fine for a
+/// short-lived test process but would leak unboundedly for a long-lived
+/// embedder that re-assembled snippets repeatedly.
+#[cfg(test)]
+pub(crate) fn assemble(source: &str) -> Result<Assembly, AssemblyParseError> {
+    let mut assembly = Assembly::new(0);
+    let mut labels: HashMap<String, Label> = HashMap::default();
+
+    for line in source.lines() {
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(hash) = line.strip_prefix("extern 0x").and_then(|s| s.strip_suffix(';')) {
+            let hash = parse_hash(hash, line)?;
+            assembly.required_functions.entry(hash).or_default();
+            continue;
+        }
+
+        if let Some(name) = line.strip_suffix(':') {
+            let label = label_named(&mut assembly, &mut labels, name);
+            assembly
+                .label(label)
+                .map_err(|_| AssemblyParseError::new(line))?;
+            continue;
+        }
+
+        let (code, comment) = match line.split_once(';') {
+            Some((code, comment)) => (code.trim(), Some(comment.trim())),
+            None => (line, None),
+        };
+
+        let mut parts = code.split_whitespace();
+        let mnemonic = next_arg(&mut parts, line)?;
+        let span = Span::default();
+
+        match mnemonic {
+            "jump" => {
+                let label = label_named(&mut assembly, &mut labels, next_arg(&mut parts, line)?);
+                assembly.jump(label, span);
+            }
+            "jump_if" => {
+                let label = label_named(&mut assembly, &mut labels, next_arg(&mut parts, line)?);
+                assembly.jump_if(label, span);
+            }
+            "jump_if_not" => {
+                let label = label_named(&mut assembly, &mut labels, next_arg(&mut parts, line)?);
+                assembly.jump_if_not(label, span);
+            }
+            "jump_if_branch" => {
+                let branch = parse_i64(next_arg(&mut parts, line)?, line)?;
+                let label = label_named(&mut assembly, &mut labels, next_arg(&mut parts, line)?);
+                assembly.jump_if_branch(branch, label, span);
+            }
+            "pop_and_jump_if" => {
+                let count = parse_usize(next_arg(&mut parts, line)?, line)?;
+                let label = label_named(&mut assembly, &mut labels, next_arg(&mut parts, line)?);
+                assembly.pop_and_jump_if(count, label, span);
+            }
+            "pop_and_jump_if_not" => {
+                let count = parse_usize(next_arg(&mut parts, line)?, line)?;
+                let label = label_named(&mut assembly, &mut labels, next_arg(&mut parts, line)?);
+                assembly.pop_and_jump_if_not(count, label, span);
+            }
+            "call" => {
+                let hex = next_arg(&mut parts, line)?
+                    .strip_prefix("0x")
+                    .ok_or_else(|| AssemblyParseError::new(line))?;
+                let hash = parse_hash(hex, line)?;
+                // The number of arguments a call takes isn't part of the
+                // textual form, so it round-trips as zero.
+                assembly.push(Inst::Call { hash, args: 0 }, span);
+            }
+            "Return" => assembly.push(Inst::Return, span),
+            _ => return Err(AssemblyParseError::new(line)),
+        }
+
+        if let Some(comment) = comment {
+            assembly
+                .comments
+                .entry(assembly.instructions.len() - 1)
+                .or_default()
+                .push(comment.to_owned());
+        }
+    }
+
+    Ok(assembly)
+}
+
+/// Look up or create the label registered under `name`.
+///
+/// `disassemble` renders a label through `Label`'s `Display` impl, which
+/// concatenates its `name` and `ident` as `{name}_{ident}`. To round-trip
+/// that exactly, a name ending in `_<digits>` is split back into those two
+/// parts and given back its original `ident`, instead of being treated as
+/// one opaque string with a fresh `ident` - which would re-render as
+/// `{name}_{ident}_<fresh ident>` and break every label in the output.
+/// Either way the backing string is leaked to satisfy `new_label`'s
+/// `&'static str` requirement; see the `#[cfg(test)]` gate on [`assemble`].
+#[cfg(test)]
+fn label_named(assembly: &mut Assembly, labels: &mut HashMap<String, Label>, name: &str) -> Label {
+    if let Some(&label) = labels.get(name) {
+        return label;
+    }
+
+    let label = match name.rsplit_once('_') {
+        Some((prefix, ident)) if !ident.is_empty() && ident.bytes().all(|b| b.is_ascii_digit()) => {
+            let ident: usize = ident.parse().expect("validated above");
+            assembly.label_count = assembly.label_count.max(ident + 1);
+            Label {
+                name: Box::leak(prefix.to_owned().into_boxed_str()),
+                ident,
+            }
+        }
+        _ => assembly.new_label(Box::leak(name.to_owned().into_boxed_str())),
+    };
+
+    labels.insert(name.to_owned(), label);
+    label
+}
+
+#[cfg(test)]
+fn next_arg<'a>(
+    parts: &mut impl Iterator<Item = &'a str>,
+    line: &str,
+) -> Result<&'a str, AssemblyParseError> {
+    parts.next().ok_or_else(|| AssemblyParseError::new(line))
+}
+
+#[cfg(test)]
+fn parse_hash(hex: &str, line: &str) -> Result<Hash, AssemblyParseError> {
+    let value = u64::from_str_radix(hex, 16).map_err(|_| AssemblyParseError::new(line))?;
+    Ok(Hash::from(value))
+}
+
+#[cfg(test)]
+fn parse_i64(value: &str, line: &str) -> Result<i64, AssemblyParseError> {
+    value.parse().map_err(|_| AssemblyParseError::new(line))
+}
+
+#[cfg(test)]
+fn parse_usize(value: &str, line: &str) -> Result<usize, AssemblyParseError> {
+    value.parse().map_err(|_| AssemblyParseError::new(line))
+}
+
+/// An error produced while parsing the textual assembly format emitted by
+/// [`Assembly::disassemble`].
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub(crate) struct AssemblyParseError {
+    line: String,
+}
+
+#[cfg(test)]
+impl AssemblyParseError {
+    fn new(line: impl Into<String>) -> Self {
+        Self { line: line.into() }
+    }
+}
+
+#[cfg(test)]
+impl fmt::Display for AssemblyParseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "failed to parse assembly line: `{}`", self.line)
+    }
+}
+
+#[cfg(test)]
+impl std::error::Error for AssemblyParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_jumps_through_a_cycle() {
+        let mut asm = Assembly::new(0);
+        let a = asm.new_label("a");
+        let b = asm.new_label("b");
+
+        asm.label(a).unwrap(); // offset 0
+        asm.jump(b, Span::default()); // 0: jump b
+
+        asm.label(b).unwrap(); // offset 1
+        asm.jump(a, Span::default()); // 1: jump a
+
+        // Must terminate rather than looping forever on the a -> b -> a
+        // cycle, and settle on a consistent target for both jumps.
+        asm.thread_jumps();
+
+        for (inst, _) in &asm.instructions {
+            match inst {
+                AssemblyInst::Jump { label } => assert_eq!(*label, a),
+                other => panic!("unexpected instruction: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn eliminate_dead_code_past_a_return() {
+        let mut asm = Assembly::new(0);
+        let after = asm.new_label("after");
+
+        asm.push(Inst::Return, Span::default()); // 0: return
+        asm.push(Inst::Return, Span::default()); // 1: unreachable, dropped
+
+        asm.label(after).unwrap(); // offset 2, stops the drop
+        asm.push(Inst::Return, Span::default()); // 2: return
+
+        asm.eliminate_dead_code();
+
+        assert_eq!(asm.instructions.len(), 2);
+        assert_eq!(asm.labels.get(&after), Some(&1));
+    }
+
+    #[test]
+    fn peephole_collapses_jump_if_then_jump() {
+        let mut asm = Assembly::new(0);
+        let l = asm.new_label("l");
+        let m = asm.new_label("m");
+
+        asm.jump_if(l, Span::default()); // 0: jump_if l
+        asm.jump(m, Span::default()); // 1: jump m
+
+        asm.label(l).unwrap(); // offset 2, the fall-through target
+        asm.push(Inst::Return, Span::default()); // 2: return
+
+        asm.peephole();
+
+        assert_eq!(asm.instructions.len(), 2);
+        assert!(matches!(
+            asm.instructions[0].0,
+            AssemblyInst::JumpIfNot { label } if label == m
+        ));
+    }
+
+    #[test]
+    fn peephole_keeps_an_independently_labeled_jump() {
+        // Same shape as `peephole_collapses_jump_if_then_jump`, except the
+        // `Jump m` in the middle is also the target of an unrelated jump.
+        // Collapsing it would silently repoint that other jump.
+        let mut asm = Assembly::new(0);
+        let l = asm.new_label("l");
+        let m = asm.new_label("m");
+        let elsewhere = asm.new_label("elsewhere");
+
+        asm.jump_if(l, Span::default()); // 0: jump_if l
+
+        asm.label(elsewhere).unwrap(); // offset 1
+        asm.jump(m, Span::default()); // 1: jump m, labeled `elsewhere`
+
+        asm.label(l).unwrap(); // offset 2, the fall-through target
+        asm.push(Inst::Return, Span::default()); // 2: return
+
+        asm.jump(elsewhere, Span::default()); // 3: jump elsewhere
+
+        asm.peephole();
+
+        assert_eq!(asm.instructions.len(), 4);
+        assert_eq!(asm.labels.get(&elsewhere), Some(&1));
+        assert!(matches!(
+            asm.instructions[1].0,
+            AssemblyInst::Jump { label } if label == m
+        ));
+    }
+
+    #[test]
+    fn disassemble_assemble_round_trip_is_stable() {
+        let mut asm = Assembly::new(0);
+        let top = asm.new_label("top");
+        let done = asm.new_label("done");
+
+        asm.label(top).unwrap();
+        asm.jump_if_not(done, Span::default());
+        asm.push(Inst::Return, Span::default());
+        asm.jump(top, Span::default());
+        asm.label(done).unwrap();
+
+        let mut first = String::new();
+        asm.disassemble(&mut first).unwrap();
+
+        let reassembled = assemble(&first).unwrap();
+
+        let mut second = String::new();
+        reassembled.disassemble(&mut second).unwrap();
+
+        assert_eq!(first, second);
+    }
 }